@@ -0,0 +1,123 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use crate::link::LinkStatus;
+
+/// A file and line number that references a URL.
+pub type Site = (String, usize);
+
+/// Everything we know about one URL: every site that references it, and its
+/// (shared) verification status.
+pub struct HashVal {
+    pub sites: HashSet<Site>,
+    pub status: LinkStatus,
+}
+
+/// A thread-safe map from URL to `HashVal`, so that a URL appearing at many
+/// sites only gets fetched once.
+#[derive(Default)]
+pub struct UrlHash {
+    inner: Mutex<HashMap<String, HashVal>>,
+}
+
+impl UrlHash {
+    pub fn new() -> Self {
+        UrlHash {
+            inner: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Record that `url` was referenced at `site`, inserting it with
+    /// `LinkStatus::Unknown` if this is the first time we've seen it.
+    ///
+    /// Returns `true` the first time a URL is seen, so the caller knows it
+    /// still needs to dispatch a verify job; on every later call for the
+    /// same URL the site is just appended and no job is dispatched.
+    pub fn register(&self, url: &str, site: Site) -> bool {
+        let mut map = self.inner.lock().unwrap();
+        match map.get_mut(url) {
+            Some(val) => {
+                val.sites.insert(site);
+                false
+            }
+            None => {
+                let mut sites = HashSet::new();
+                sites.insert(site);
+                map.insert(
+                    url.to_string(),
+                    HashVal {
+                        sites,
+                        status: LinkStatus::Unknown,
+                    },
+                );
+                true
+            }
+        }
+    }
+
+    /// Fill in the status for a URL once its verify job has finished.
+    pub fn set_status(&self, url: &str, status: LinkStatus) {
+        let mut map = self.inner.lock().unwrap();
+        if let Some(val) = map.get_mut(url) {
+            val.status = status;
+        }
+    }
+
+    pub fn into_inner(self) -> HashMap<String, HashVal> {
+        self.inner.into_inner().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn register_returns_true_only_the_first_time_a_url_is_seen() {
+        let cache = UrlHash::new();
+        assert!(cache.register("https://a", ("a.md".to_string(), 1)));
+        assert!(!cache.register("https://a", ("b.md".to_string(), 2)));
+        assert!(cache.register("https://b", ("a.md".to_string(), 3)));
+    }
+
+    #[test]
+    fn register_accumulates_sites_for_the_same_url() {
+        let cache = UrlHash::new();
+        cache.register("https://a", ("a.md".to_string(), 1));
+        cache.register("https://a", ("b.md".to_string(), 2));
+
+        let map = cache.into_inner();
+        let sites = &map["https://a"].sites;
+        assert_eq!(sites.len(), 2);
+        assert!(sites.contains(&("a.md".to_string(), 1)));
+        assert!(sites.contains(&("b.md".to_string(), 2)));
+    }
+
+    #[test]
+    fn new_url_starts_unknown() {
+        let cache = UrlHash::new();
+        cache.register("https://a", ("a.md".to_string(), 1));
+        let map = cache.into_inner();
+        assert!(matches!(map["https://a"].status, LinkStatus::Unknown));
+    }
+
+    #[test]
+    fn set_status_updates_only_the_matching_url() {
+        let cache = UrlHash::new();
+        cache.register("https://a", ("a.md".to_string(), 1));
+        cache.register("https://b", ("a.md".to_string(), 2));
+
+        cache.set_status("https://a", LinkStatus::Reachable);
+
+        let map = cache.into_inner();
+        assert!(matches!(map["https://a"].status, LinkStatus::Reachable));
+        assert!(matches!(map["https://b"].status, LinkStatus::Unknown));
+    }
+
+    #[test]
+    fn set_status_on_an_unregistered_url_is_a_no_op() {
+        let cache = UrlHash::new();
+        cache.set_status("https://never-registered", LinkStatus::Reachable);
+        assert!(cache.into_inner().is_empty());
+    }
+}
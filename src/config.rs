@@ -0,0 +1,37 @@
+use std::path::Path;
+
+use serde_derive::Deserialize;
+
+/// Settings read from `.cargo-links.toml`, merged with whatever was passed
+/// on the command line.
+#[derive(Debug, Default, Deserialize)]
+pub struct Config {
+    /// Glob patterns matched against a link's URL; a match is skipped
+    /// entirely instead of being fetched.
+    #[serde(default)]
+    pub ignore: Vec<String>,
+
+    /// Status codes that should count as reachable even though they're not
+    /// in the 2xx range (e.g. a host that returns 403 to bots).
+    #[serde(default)]
+    pub accept: Vec<u16>,
+}
+
+impl Config {
+    /// Load the config file at `path`, if it exists; a missing file yields
+    /// the default (empty) config.
+    pub fn load(path: &Path) -> Result<Config, failure::Error> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => Ok(toml::from_str(&contents)?),
+            Err(ref e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Fold in patterns/codes passed on the command line.
+    pub fn merge(mut self, ignore: Vec<String>, accept: Vec<u16>) -> Config {
+        self.ignore.extend(ignore);
+        self.accept.extend(accept);
+        self
+    }
+}
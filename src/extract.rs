@@ -0,0 +1,280 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use linkify::{LinkFinder, LinkKind};
+use pulldown_cmark::{Event, Options, Parser, Tag};
+
+/// A link destination found in a source file, with the (1-indexed) line it
+/// appeared on.
+pub struct Extracted {
+    pub dest: String,
+    pub line: usize,
+}
+
+/// Pull every link destination out of a file's contents. Markdown files get
+/// a full CommonMark parse, so inline links, images, and reference-style
+/// links are all covered; Rust files are scanned for bare URLs inside
+/// `///`/`//!` doc comments. Anything else yields no links.
+pub fn extract(path: &Path, contents: &str) -> Vec<Extracted> {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("md") => extract_markdown(contents),
+        Some("rs") => extract_doc_comments(contents),
+        _ => Vec::new(),
+    }
+}
+
+fn line_of(contents: &str, offset: usize) -> usize {
+    contents[..offset].bytes().filter(|&b| b == b'\n').count() + 1
+}
+
+fn extract_markdown(contents: &str) -> Vec<Extracted> {
+    let mut finder = LinkFinder::new();
+    finder.kinds(&[LinkKind::Url]);
+
+    let mut links = Vec::new();
+    let mut code_lines = HashSet::new();
+    let mut in_code_block = false;
+    let mut in_link = false;
+
+    for (event, range) in Parser::new_ext(contents, Options::empty()).into_offset_iter() {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(Tag::CodeBlock(_)) => in_code_block = false,
+            Event::Start(Tag::Link(..)) => in_link = true,
+            Event::End(Tag::Link(..)) => in_link = false,
+            _ => {}
+        }
+
+        // Track which lines fall inside a fenced or indented code block, so
+        // the reference-definition scan below doesn't mistake an example of
+        // `[foo]: bar` *shown* in a code block for a real one.
+        if in_code_block {
+            if let Event::Text(_) = event {
+                let first = line_of(contents, range.start);
+                let last = line_of(contents, range.end.saturating_sub(1));
+                code_lines.extend(first..=last);
+            }
+        }
+
+        // Raw HTML (e.g. a badge wrapped in `<a href="...">`) doesn't get a
+        // `Tag::Link` of its own; pulldown-cmark just hands back the HTML
+        // text verbatim as an `Html`/`InlineHtml` event, so pull `href`s out
+        // of it ourselves.
+        let dests = match &event {
+            Event::Start(Tag::Link(_, dest, _)) | Event::Start(Tag::Image(_, dest, _)) => {
+                vec![dest.to_string()]
+            }
+            Event::Html(text) | Event::InlineHtml(text) => extract_hrefs(text),
+            // A bare `https://...` in prose never becomes a `Tag::Link` (only
+            // `<https://...>` autolinks do), so without this pulldown-cmark
+            // would silently drop it. Skip code blocks (examples, not real
+            // links) and link text (already covered by the `Tag::Link` case
+            // above, and a `[https://x][y]`-style link would otherwise be
+            // reported twice).
+            Event::Text(text) if !in_code_block && !in_link => {
+                finder.links(text).map(|link| link.as_str().to_string()).collect()
+            }
+            _ => Vec::new(),
+        };
+        for dest in dests {
+            links.push(Extracted {
+                dest,
+                line: line_of(contents, range.start),
+            });
+        }
+    }
+
+    // pulldown-cmark resolves reference-style links (`[text][ref]`) to their
+    // destination as part of the events above, but a reference definition
+    // that's never actually used (`[ref]: https://...`) doesn't emit an
+    // event at all, so it would otherwise go unchecked. This scan reports
+    // every definition outside a code block, used or not, so a used one
+    // shows up both where it's used and on its own definition line.
+    for (lnum, line) in contents.lines().enumerate() {
+        let lnum = lnum + 1;
+        if code_lines.contains(&lnum) {
+            continue;
+        }
+        if let Some(dest) = reference_definition_dest(line) {
+            links.push(Extracted { dest, line: lnum });
+        }
+    }
+
+    links
+}
+
+/// Pull every `href="..."` (or `href='...'`) attribute value out of a chunk
+/// of raw HTML, e.g. `<a href="https://example.com">`.
+fn extract_hrefs(html: &str) -> Vec<String> {
+    let mut hrefs = Vec::new();
+    let mut rest = html;
+    while let Some(idx) = rest.find("href=") {
+        rest = &rest[idx + "href=".len()..];
+        let quote = match rest.as_bytes().first() {
+            Some(b'"') => '"',
+            Some(b'\'') => '\'',
+            _ => continue,
+        };
+        rest = &rest[1..];
+        match rest.find(quote) {
+            Some(end) => {
+                hrefs.push(rest[..end].to_string());
+                rest = &rest[end + 1..];
+            }
+            None => break,
+        }
+    }
+    hrefs
+}
+
+/// Match a standalone link-reference definition, e.g. `[foo]: some-dest`.
+/// The destination must be a single token, optionally followed by a quoted
+/// title (`[foo]: some-dest "title"`) — anything else after it is prose
+/// rather than a link, as in a footnote definition
+/// (`[^1]: Footnote text explaining the claim.`), which shares the same
+/// `[label]: ...` shape but isn't a link at all.
+fn reference_definition_dest(line: &str) -> Option<String> {
+    let rest = line.trim_start().strip_prefix('[')?;
+    let close = rest.find(']')?;
+    let rest = rest[close + 1..].trim_start().strip_prefix(':')?.trim_start();
+
+    let mut parts = rest.splitn(2, char::is_whitespace);
+    let dest = parts.next().filter(|dest| !dest.is_empty())?;
+
+    if let Some(remainder) = parts.next() {
+        let remainder = remainder.trim_start();
+        let is_title = remainder.starts_with('"') || remainder.starts_with('\'') || remainder.starts_with('(');
+        if !remainder.is_empty() && !is_title {
+            return None;
+        }
+    }
+
+    Some(dest.to_string())
+}
+
+fn extract_doc_comments(contents: &str) -> Vec<Extracted> {
+    let mut finder = LinkFinder::new();
+    finder.kinds(&[LinkKind::Url]);
+
+    let mut links = Vec::new();
+    for (lnum, line) in contents.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if !(trimmed.starts_with("///") || trimmed.starts_with("//!")) {
+            continue;
+        }
+        for link in finder.links(line) {
+            links.push(Extracted {
+                dest: link.as_str().to_string(),
+                line: lnum + 1,
+            });
+        }
+    }
+    links
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dests(extracted: Vec<Extracted>) -> Vec<String> {
+        extracted.into_iter().map(|e| e.dest).collect()
+    }
+
+    #[test]
+    fn extract_dispatches_on_extension() {
+        assert!(!extract(Path::new("a.md"), "[x](./y)").is_empty());
+        assert!(!extract(Path::new("a.rs"), "/// https://example.com\n").is_empty());
+        assert!(extract(Path::new("a.txt"), "[x](./y)").is_empty());
+    }
+
+    #[test]
+    fn extract_markdown_finds_inline_and_reference_links() {
+        let contents = "[inline](./a.md)\n\n[ref link][ref]\n\n[ref]: ./b.md\n";
+        let links = extract_markdown(contents);
+        assert_eq!(dests(links), vec!["./a.md", "./b.md", "./b.md"]);
+    }
+
+    #[test]
+    fn extract_markdown_ignores_unused_reference_definitions_inside_code_blocks() {
+        let contents = "```markdown\n[foo]: https://example.com\n```\n\n    [bar]: ./nonexistent.md\n";
+        assert!(extract_markdown(contents).is_empty());
+    }
+
+    #[test]
+    fn extract_markdown_still_catches_unused_reference_definitions_outside_code_blocks() {
+        let contents = "[foo]: ./nonexistent.md\n";
+        let links = extract_markdown(contents);
+        assert_eq!(dests(links), vec!["./nonexistent.md"]);
+    }
+
+    #[test]
+    fn reference_definition_dest_matches_standalone_definitions() {
+        assert_eq!(
+            reference_definition_dest("[foo]: ./bar.md"),
+            Some("./bar.md".to_string())
+        );
+        assert_eq!(
+            reference_definition_dest("[foo]: ./bar.md \"Title\""),
+            Some("./bar.md".to_string())
+        );
+        assert_eq!(reference_definition_dest("not a definition"), None);
+    }
+
+    #[test]
+    fn reference_definition_dest_ignores_footnote_definitions() {
+        // Shares the `[label]: ...` shape of a link-reference definition,
+        // but the body is prose, not a destination.
+        assert_eq!(
+            reference_definition_dest("[^1]: Footnote text explaining the claim."),
+            None
+        );
+    }
+
+    #[test]
+    fn extract_markdown_ignores_footnote_definitions() {
+        let contents = "See this claim.[^1]\n\n[^1]: Footnote text explaining the claim.\n";
+        assert!(extract_markdown(contents).is_empty());
+    }
+
+    #[test]
+    fn extract_markdown_finds_bare_urls_in_prose() {
+        let contents = "See https://bare-url-in-prose.example for details.\n";
+        assert_eq!(
+            dests(extract_markdown(contents)),
+            vec!["https://bare-url-in-prose.example"]
+        );
+    }
+
+    #[test]
+    fn extract_markdown_does_not_double_count_a_link_whose_text_is_its_own_url() {
+        let contents = "[https://example.com](https://example.com)\n";
+        assert_eq!(
+            dests(extract_markdown(contents)),
+            vec!["https://example.com"]
+        );
+    }
+
+    #[test]
+    fn extract_markdown_ignores_bare_urls_inside_code_blocks() {
+        let contents = "```\nSee https://example.com for an example.\n```\n";
+        assert!(extract_markdown(contents).is_empty());
+    }
+
+    #[test]
+    fn extract_markdown_finds_raw_html_hrefs() {
+        let contents = "<a href=\"https://example.com/raw\">here</a>\n<a href='./local.md'>local</a>\n";
+        assert_eq!(
+            dests(extract_markdown(contents)),
+            vec!["https://example.com/raw", "./local.md"]
+        );
+    }
+
+    #[test]
+    fn extract_doc_comments_finds_bare_urls_in_doc_lines_only() {
+        let contents = "// https://not-a-doc-comment.example\n/// see https://example.com\n//! also https://example.org\nlet x = 1;\n";
+        assert_eq!(
+            dests(extract_doc_comments(contents)),
+            vec!["https://example.com", "https://example.org"]
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Condvar, Mutex};
+
+/// Caps the number of concurrent requests to any single host, so a handful
+/// of links to the same slow or rate-limited host don't starve the thread
+/// pool or trip that host's own rate limiting.
+pub struct HostLimiter {
+    max_per_host: usize,
+    counts: Mutex<HashMap<String, usize>>,
+    cond: Condvar,
+}
+
+impl HostLimiter {
+    pub fn new(max_per_host: usize) -> Arc<Self> {
+        Arc::new(HostLimiter {
+            max_per_host,
+            counts: Mutex::new(HashMap::new()),
+            cond: Condvar::new(),
+        })
+    }
+}
+
+/// Block until a request slot for `host` is free, then hold it until the
+/// returned guard is dropped.
+pub fn acquire(limiter: Arc<HostLimiter>, host: String) -> HostGuard {
+    let mut counts = limiter.counts.lock().unwrap();
+    loop {
+        let count = counts.entry(host.clone()).or_insert(0);
+        if *count < limiter.max_per_host {
+            *count += 1;
+            break;
+        }
+        counts = limiter.cond.wait(counts).unwrap();
+    }
+    drop(counts);
+    HostGuard { limiter, host }
+}
+
+pub struct HostGuard {
+    limiter: Arc<HostLimiter>,
+    host: String,
+}
+
+impl Drop for HostGuard {
+    fn drop(&mut self) {
+        let mut counts = self.limiter.counts.lock().unwrap();
+        if let Some(count) = counts.get_mut(&self.host) {
+            *count -= 1;
+        }
+        self.limiter.cond.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn acquire_does_not_block_across_different_hosts() {
+        let limiter = HostLimiter::new(1);
+        let _a = acquire(limiter.clone(), "a".to_string());
+        let _b = acquire(limiter, "b".to_string());
+    }
+
+    #[test]
+    fn acquire_blocks_until_the_holding_guard_for_the_same_host_is_dropped() {
+        let limiter = HostLimiter::new(1);
+        let guard = acquire(limiter.clone(), "host".to_string());
+
+        let (tx, rx) = mpsc::channel();
+        let waiter = thread::spawn(move || {
+            let _guard = acquire(limiter, "host".to_string());
+            tx.send(()).unwrap();
+        });
+
+        assert!(
+            rx.recv_timeout(Duration::from_millis(200)).is_err(),
+            "acquire should still be blocked on the held slot"
+        );
+
+        drop(guard);
+        rx.recv_timeout(Duration::from_secs(1))
+            .expect("acquire should unblock once the slot is released");
+        waiter.join().unwrap();
+    }
+}
@@ -0,0 +1,445 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "network")]
+use std::collections::HashSet;
+#[cfg(feature = "network")]
+use std::sync::Arc;
+#[cfg(feature = "network")]
+use std::thread;
+#[cfg(feature = "network")]
+use std::time::Duration;
+
+#[cfg(feature = "network")]
+use globset::GlobSet;
+#[cfg(feature = "network")]
+use percent_encoding::percent_decode_str;
+#[cfg(feature = "network")]
+use reqwest::header::{CONTENT_TYPE, RETRY_AFTER};
+#[cfg(feature = "network")]
+use reqwest::StatusCode;
+#[cfg(feature = "network")]
+use scraper::{Html, Selector};
+
+#[cfg(feature = "network")]
+use crate::limiter::HostLimiter;
+
+/// How many times a retryable failure (a timeout, or a 429/5xx response) is
+/// retried before giving up on a URL.
+#[cfg(feature = "network")]
+const MAX_RETRIES: u32 = 3;
+
+/// The base of the exponential backoff between retries; the actual delay is
+/// `BASE_BACKOFF * 2.pow(attempt)`, unless the response names a longer delay
+/// via `Retry-After`.
+#[cfg(feature = "network")]
+const BASE_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Everything `Link::verify` needs to check a network link: the HTTP client,
+/// a per-host concurrency limiter, and the user's ignore/accept settings.
+#[cfg(feature = "network")]
+pub struct Verifier {
+    pub client: Arc<reqwest::Client>,
+    pub limiter: Arc<HostLimiter>,
+    pub ignore: GlobSet,
+    pub accept: HashSet<u16>,
+    pub offline: bool,
+}
+#[cfg(not(feature = "network"))]
+pub struct Verifier;
+
+/// The outcome of checking a single URL.
+#[derive(Debug, Clone)]
+pub enum LinkStatus {
+    /// Not yet verified. Used as the initial state while a link sits in the
+    /// cache waiting for its verify job to run.
+    Unknown,
+    Reachable,
+    Questionable(String),
+    Unreachable(Option<String>),
+}
+
+/// Where a link points: either out to the network, to a path relative to the
+/// file that referenced it (e.g. `../README.md` or `./src/lib.rs#L20`), or to
+/// a non-http(s) scheme (`mailto:`, `tel:`, `data:`, ...) we have no way to
+/// verify.
+#[derive(Debug, Clone)]
+pub enum LinkKind {
+    Network(String),
+    Relative { path: PathBuf, line: Option<usize> },
+    Unsupported { dest: String, scheme: String },
+}
+
+impl LinkKind {
+    /// Classify a raw link destination found in `referencing_file`, resolving
+    /// relative destinations against that file's directory.
+    pub fn classify(dest: &str, referencing_file: &Path) -> LinkKind {
+        // `Url::parse` alone isn't enough: it happily accepts things like
+        // `C:\Users\foo\bar.txt` as a URL with scheme `c`, so a Windows-style
+        // path written as a link destination would otherwise be reported as
+        // an unsupported scheme. A single-letter "scheme" is always a drive
+        // letter, not a real one, so keep treating those as relative paths.
+        if let Ok(url) = url::Url::parse(dest) {
+            match url.scheme() {
+                "http" | "https" => return LinkKind::Network(dest.to_string()),
+                scheme if scheme.len() > 1 => {
+                    return LinkKind::Unsupported {
+                        dest: dest.to_string(),
+                        scheme: scheme.to_string(),
+                    };
+                }
+                _ => {}
+            }
+        }
+
+        let (path_part, fragment) = match dest.find('#') {
+            Some(idx) => (&dest[..idx], Some(&dest[idx + 1..])),
+            None => (dest, None),
+        };
+        let line = fragment.and_then(|f| f.strip_prefix('L')).and_then(|n| n.parse().ok());
+
+        let base = referencing_file.parent().unwrap_or_else(|| Path::new("."));
+        LinkKind::Relative {
+            path: base.join(path_part),
+            line,
+        }
+    }
+
+    /// A string that uniquely identifies this link's target: the raw URL for
+    /// network links, or the resolved filesystem path for relative ones, so
+    /// the same target is only ever verified once. The path is the
+    /// referencing file's directory joined with the link's path and is not
+    /// normalized, so two different spellings of the same file (e.g.
+    /// `./docs/../README.md` and `./README.md`) get separate keys.
+    pub fn cache_key(&self) -> String {
+        match self {
+            LinkKind::Network(url) => url.clone(),
+            LinkKind::Relative { path, line } => match line {
+                Some(l) => format!("{}#L{}", path.display(), l),
+                None => path.display().to_string(),
+            },
+            LinkKind::Unsupported { dest, .. } => dest.clone(),
+        }
+    }
+}
+
+/// A single link destination to be checked, already classified.
+#[derive(Debug)]
+pub struct Link {
+    pub kind: LinkKind,
+    pub status: Option<LinkStatus>,
+}
+
+impl Link {
+    pub fn new(kind: LinkKind) -> Self {
+        Link { kind, status: None }
+    }
+
+    /// Check this link. Relative links are always resolved against the
+    /// filesystem, never the network; network links are skipped (and
+    /// reported as `Questionable`) when `verifier.offline` is set or the
+    /// crate was built without the `network` feature.
+    pub fn verify(&mut self, verifier: &Verifier) {
+        self.status = Some(match &self.kind {
+            LinkKind::Relative { path, line } => Self::verify_relative(path, *line),
+            LinkKind::Network(url) => Self::verify_network(url, verifier),
+            LinkKind::Unsupported { scheme, .. } => {
+                LinkStatus::Questionable(format!("unsupported scheme: {}", scheme))
+            }
+        });
+    }
+
+    /// Assert that a relative path (and, if given, a specific line within
+    /// it) exists on disk, without touching the network.
+    fn verify_relative(path: &Path, line: Option<usize>) -> LinkStatus {
+        if !path.exists() {
+            return LinkStatus::Unreachable(Some("path does not exist".to_string()));
+        }
+        if let Some(line) = line {
+            match std::fs::read_to_string(path) {
+                Ok(contents) if contents.lines().count() < line => {
+                    return LinkStatus::Unreachable(Some(format!(
+                        "file has fewer than {} lines",
+                        line
+                    )));
+                }
+                Err(_) => {
+                    // Probably not UTF-8 text (e.g. a binary file); the path
+                    // itself exists, we just can't check the line number.
+                    return LinkStatus::Questionable(format!(
+                        "couldn't read {} to verify line {}",
+                        path.display(),
+                        line
+                    ));
+                }
+                Ok(_) => {}
+            }
+        }
+        LinkStatus::Reachable
+    }
+
+    #[cfg(feature = "network")]
+    fn verify_network(url: &str, verifier: &Verifier) -> LinkStatus {
+        if verifier.offline {
+            return LinkStatus::Questionable(format!(
+                "--offline: skipped network check for {}",
+                url
+            ));
+        }
+        if verifier.ignore.is_match(url) {
+            return LinkStatus::Questionable(format!("ignored by config: {}", url));
+        }
+
+        let (base, fragment) = match url.find('#') {
+            Some(idx) => (&url[..idx], Some(&url[idx + 1..])),
+            None => (url, None),
+        };
+
+        let host = match url::Url::parse(base).ok().and_then(|u| u.host_str().map(String::from)) {
+            Some(host) => host,
+            None => return LinkStatus::Unreachable(Some("couldn't determine host".to_string())),
+        };
+        let _permit = crate::limiter::acquire(verifier.limiter.clone(), host);
+
+        let mut attempt = 0;
+        loop {
+            match verifier.client.get(base).send() {
+                Ok(mut response) => {
+                    let status = response.status();
+                    if status.is_success() || verifier.accept.contains(&status.as_u16()) {
+                        return match fragment {
+                            Some(fragment) => Self::verify_fragment_of(&mut response, fragment),
+                            None => LinkStatus::Reachable,
+                        };
+                    }
+
+                    let retryable = status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error();
+                    if retryable && attempt < MAX_RETRIES {
+                        thread::sleep(retry_delay(&response, attempt));
+                        attempt += 1;
+                        continue;
+                    }
+                    return if retryable {
+                        LinkStatus::Questionable(format!(
+                            "still failing after {} retries: status code {}",
+                            MAX_RETRIES, status
+                        ))
+                    } else {
+                        LinkStatus::Unreachable(Some(format!("status code {}", status)))
+                    };
+                }
+                Err(e) => {
+                    if e.is_timeout() && attempt < MAX_RETRIES {
+                        thread::sleep(retry_delay_without_response(attempt));
+                        attempt += 1;
+                        continue;
+                    }
+                    return if e.is_timeout() {
+                        LinkStatus::Questionable(format!(
+                            "still timing out after {} retries",
+                            MAX_RETRIES
+                        ))
+                    } else {
+                        LinkStatus::Unreachable(Some(e.to_string()))
+                    };
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "network")]
+    fn verify_fragment_of(response: &mut reqwest::Response, fragment: &str) -> LinkStatus {
+        let is_html = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(|ct| ct.contains("html"))
+            .unwrap_or(false);
+
+        if !is_html {
+            return LinkStatus::Questionable(format!(
+                "response doesn't look like HTML, can't verify anchor #{}",
+                fragment
+            ));
+        }
+
+        match response.text() {
+            Ok(body) => verify_fragment(&body, fragment),
+            Err(e) => LinkStatus::Questionable(format!(
+                "couldn't read response body to verify anchor #{}: {}",
+                fragment, e
+            )),
+        }
+    }
+
+    #[cfg(not(feature = "network"))]
+    fn verify_network(url: &str, _verifier: &Verifier) -> LinkStatus {
+        LinkStatus::Questionable(format!(
+            "built without the `network` feature, couldn't verify {}",
+            url
+        ))
+    }
+}
+
+/// How long to wait before retrying a request that got a 429/5xx response:
+/// honors `Retry-After` (in seconds) if the server sent one, otherwise falls
+/// back to an exponential backoff.
+#[cfg(feature = "network")]
+fn retry_delay(response: &reqwest::Response, attempt: u32) -> Duration {
+    response
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(retry_after_duration)
+        .unwrap_or_else(|| retry_delay_without_response(attempt))
+}
+
+/// Parse a `Retry-After` header value (in seconds; the HTTP-date form isn't
+/// supported) into a `Duration`, split out from `retry_delay` so it can be
+/// tested without a live response.
+#[cfg(feature = "network")]
+fn retry_after_duration(value: &str) -> Option<Duration> {
+    value.parse::<u64>().ok().map(Duration::from_secs)
+}
+
+#[cfg(feature = "network")]
+fn retry_delay_without_response(attempt: u32) -> Duration {
+    BASE_BACKOFF * 2u32.pow(attempt)
+}
+
+/// Check that `fragment` names an anchor (an `id`, or an `<a name="...">`)
+/// somewhere on `body`, and flag duplicate ids along the way since those
+/// make anchor links ambiguous.
+#[cfg(feature = "network")]
+fn verify_fragment(body: &str, fragment: &str) -> LinkStatus {
+    let wanted = percent_decode_str(fragment).decode_utf8_lossy().into_owned();
+
+    let document = Html::parse_document(body);
+    let id_selector = Selector::parse("[id]").unwrap();
+    let name_selector = Selector::parse("a[name]").unwrap();
+
+    let mut seen = HashSet::new();
+    let mut duplicated = HashSet::new();
+    for id in document
+        .select(&id_selector)
+        .filter_map(|el| el.value().attr("id"))
+    {
+        if !seen.insert(id.to_string()) {
+            duplicated.insert(id.to_string());
+        }
+    }
+    seen.extend(
+        document
+            .select(&name_selector)
+            .filter_map(|el| el.value().attr("name"))
+            .map(String::from),
+    );
+
+    if !seen.contains(&wanted) {
+        LinkStatus::Unreachable(Some(format!("anchor #{} not found", fragment)))
+    } else if duplicated.contains(&wanted) {
+        LinkStatus::Questionable(format!("id \"{}\" is duplicated on the page", wanted))
+    } else {
+        LinkStatus::Reachable
+    }
+}
+
+#[cfg(all(test, feature = "network"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verify_fragment_finds_an_existing_id() {
+        let body = r#"<html><body><div id="section-1">hi</div></body></html>"#;
+        assert!(matches!(
+            verify_fragment(body, "section-1"),
+            LinkStatus::Reachable
+        ));
+    }
+
+    #[test]
+    fn verify_fragment_finds_an_a_name_anchor() {
+        let body = r#"<html><body><a name="section-1">hi</a></body></html>"#;
+        assert!(matches!(
+            verify_fragment(body, "section-1"),
+            LinkStatus::Reachable
+        ));
+    }
+
+    #[test]
+    fn verify_fragment_reports_a_missing_anchor_as_unreachable() {
+        let body = r#"<html><body><div id="section-1">hi</div></body></html>"#;
+        assert!(matches!(
+            verify_fragment(body, "section-2"),
+            LinkStatus::Unreachable(Some(_))
+        ));
+    }
+
+    #[test]
+    fn verify_fragment_flags_a_duplicated_id_as_questionable() {
+        let body = r#"<html><body><div id="section-1"></div><div id="section-1"></div></body></html>"#;
+        assert!(matches!(
+            verify_fragment(body, "section-1"),
+            LinkStatus::Questionable(_)
+        ));
+    }
+
+    #[test]
+    fn verify_fragment_decodes_a_percent_encoded_fragment() {
+        let body = r#"<html><body><div id="a b"></div></body></html>"#;
+        assert!(matches!(
+            verify_fragment(body, "a%20b"),
+            LinkStatus::Reachable
+        ));
+    }
+
+    #[test]
+    fn classify_routes_mailto_and_tel_to_unsupported_instead_of_relative() {
+        let referencing_file = Path::new("docs/README.md");
+        assert!(matches!(
+            LinkKind::classify("mailto:foo@example.com", referencing_file),
+            LinkKind::Unsupported { scheme, .. } if scheme == "mailto"
+        ));
+        assert!(matches!(
+            LinkKind::classify("tel:+15551234567", referencing_file),
+            LinkKind::Unsupported { scheme, .. } if scheme == "tel"
+        ));
+    }
+
+    #[test]
+    fn classify_still_treats_a_windows_drive_letter_as_a_relative_path() {
+        assert!(matches!(
+            LinkKind::classify("C:\\Users\\foo\\bar.txt", Path::new("docs/README.md")),
+            LinkKind::Relative { .. }
+        ));
+    }
+
+    #[test]
+    fn retry_after_duration_parses_seconds() {
+        assert_eq!(retry_after_duration("5"), Some(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn retry_after_duration_rejects_non_numeric_values() {
+        // The HTTP-date form of `Retry-After` isn't supported.
+        assert_eq!(retry_after_duration("Wed, 21 Oct 2026 07:28:00 GMT"), None);
+    }
+
+    #[test]
+    fn retry_delay_without_response_backs_off_exponentially() {
+        assert_eq!(retry_delay_without_response(0), BASE_BACKOFF);
+        assert_eq!(retry_delay_without_response(1), BASE_BACKOFF * 2);
+        assert_eq!(retry_delay_without_response(2), BASE_BACKOFF * 4);
+    }
+}
+
+impl fmt::Display for LinkKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LinkKind::Network(url) => write!(f, "{}", url),
+            LinkKind::Relative { path, line: Some(l) } => write!(f, "{}#L{}", path.display(), l),
+            LinkKind::Relative { path, line: None } => write!(f, "{}", path.display()),
+            LinkKind::Unsupported { dest, .. } => write!(f, "{}", dest),
+        }
+    }
+}
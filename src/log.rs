@@ -0,0 +1,46 @@
+use colored::*;
+
+/// A tiny leveled logger that writes colored (or plain) lines to stderr, so
+/// stdout stays free for the `--format json` report (or anything else a
+/// caller wants to pipe elsewhere).
+#[derive(Debug)]
+pub struct Logger {
+    verbosity: usize,
+    color: bool,
+}
+
+impl Logger {
+    pub fn default(verbosity: usize, color: bool) -> Self {
+        Logger { verbosity, color }
+    }
+
+    /// Only printed when `-v` (or higher) is passed.
+    pub fn debug(&self, msg: &str) -> std::io::Result<()> {
+        if self.verbosity > 0 {
+            self.print(msg, Color::BrightBlack)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn info(&self, msg: &str) -> std::io::Result<()> {
+        self.print(msg, Color::Green)
+    }
+
+    pub fn warn(&self, msg: &str) -> std::io::Result<()> {
+        self.print(msg, Color::Yellow)
+    }
+
+    pub fn error(&self, msg: &str) -> std::io::Result<()> {
+        self.print(msg, Color::Red)
+    }
+
+    fn print(&self, msg: &str, color: Color) -> std::io::Result<()> {
+        if self.color {
+            eprintln!("{}", msg.color(color));
+        } else {
+            eprintln!("{}", msg);
+        }
+        Ok(())
+    }
+}
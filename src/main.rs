@@ -1,21 +1,46 @@
-use std::sync::mpsc::channel;
+use std::path::Path;
 use std::sync::Arc;
 
 use exitfailure::ExitFailure;
 use globset::{Glob, GlobSetBuilder};
-use grep_matcher::{Captures, Matcher};
-use grep_regex::RegexMatcher;
-use grep_searcher::sinks::UTF8;
-use grep_searcher::Searcher;
 use ignore::Walk;
+use structopt::clap::arg_enum;
 use structopt::StructOpt;
 use threadpool::ThreadPool;
 
+mod cache;
+#[cfg(feature = "network")]
+mod config;
+mod extract;
+#[cfg(feature = "network")]
+mod limiter;
 mod link;
 mod log;
+mod report;
 
-use link::{Link, LinkStatus};
+use cache::UrlHash;
+use link::{Link, LinkKind, LinkStatus, Verifier};
 use log::Logger;
+use report::{Report, ReportEntry, Summary};
+
+arg_enum! {
+    /// How to print the results: human-readable colored lines, or a single
+    /// JSON document meant for CI dashboards and other tooling.
+    #[derive(Debug)]
+    enum Format {
+        Text,
+        Json,
+    }
+}
+
+arg_enum! {
+    /// Which link statuses should cause a non-zero exit code.
+    #[derive(Debug, PartialEq)]
+    enum FailOn {
+        Unreachable,
+        Questionable,
+    }
+}
 
 #[derive(Debug, StructOpt)]
 #[structopt(
@@ -35,24 +60,83 @@ struct Opt {
     /// Don't log in color.
     #[structopt(long = "no-color")]
     no_color: bool,
+
+    /// Only check relative/local links; don't touch the network.
+    #[cfg(feature = "network")]
+    #[structopt(long = "offline")]
+    offline: bool,
+
+    /// Maximum number of concurrent requests to any single host.
+    #[cfg(feature = "network")]
+    #[structopt(long = "concurrency-per-host", default_value = "4")]
+    concurrency_per_host: usize,
+
+    /// Glob pattern to skip entirely (e.g. `https://crates.io/*`; it has to
+    /// match the whole URL); can be given
+    /// more than once. Combined with the `ignore` list in
+    /// `.cargo-links.toml`, if present.
+    #[cfg(feature = "network")]
+    #[structopt(long = "ignore")]
+    ignore: Vec<String>,
+
+    /// A status code to treat as reachable even outside the 2xx range; can
+    /// be given more than once. Combined with the `accept` list in
+    /// `.cargo-links.toml`, if present.
+    #[cfg(feature = "network")]
+    #[structopt(long = "accept")]
+    accept: Vec<u16>,
+
+    /// Output format.
+    #[structopt(
+        long = "format",
+        default_value = "text",
+        raw(possible_values = "&Format::variants()", case_insensitive = "true")
+    )]
+    format: Format,
+
+    /// Which link statuses should cause a non-zero exit code: `unreachable`
+    /// only, or `questionable` (which implies `unreachable` too).
+    #[structopt(
+        long = "fail-on",
+        default_value = "unreachable",
+        raw(possible_values = "&FailOn::variants()", case_insensitive = "true")
+    )]
+    fail_on: FailOn,
 }
 
 fn main() -> Result<(), ExitFailure> {
     let opt = Opt::from_args();
-    let mut logger = Logger::default(opt.verbose, !opt.no_color);
+    let logger = Logger::default(opt.verbose, !opt.no_color);
     logger.debug(&format!("{:?}", opt)[..])?;
 
-    // This is the regular expression we use to find links.
-    let matcher = RegexMatcher::new(r"\[[^\[\]]+\]\(([^\(\)]+)\)").unwrap();
+    // Initialize thread pool.
+    let pool = ThreadPool::new(opt.concurrency);
 
-    let mut searcher = Searcher::new();
+    #[cfg(feature = "network")]
+    let verifier = Arc::new({
+        let config = config::Config::load(Path::new(".cargo-links.toml"))?
+            .merge(opt.ignore.clone(), opt.accept.clone());
 
-    // Initialize thread pool and channel.
-    let pool = ThreadPool::new(opt.concurrency);
-    let (tx, rx) = channel();
+        let mut ignore_builder = GlobSetBuilder::new();
+        for pattern in &config.ignore {
+            ignore_builder.add(Glob::new(pattern)?);
+        }
 
-    // We'll use a single HTTP client across threads.
-    let http_client = Arc::new(reqwest::Client::new());
+        Verifier {
+            client: std::sync::Arc::new(reqwest::Client::new()),
+            limiter: limiter::HostLimiter::new(opt.concurrency_per_host),
+            ignore: ignore_builder.build()?,
+            accept: config.accept.into_iter().collect(),
+            offline: opt.offline,
+        }
+    });
+    #[cfg(not(feature = "network"))]
+    let verifier = Arc::new(Verifier);
+
+    // Every distinct URL we come across is registered here exactly once, no
+    // matter how many files (or lines) reference it, so it's only ever
+    // fetched a single time.
+    let cache = Arc::new(UrlHash::new());
 
     // We iterator through all rust and markdown files not included in your .gitignore.
     let mut glob_builder = GlobSetBuilder::new();
@@ -68,10 +152,13 @@ fn main() -> Result<(), ExitFailure> {
         .map(|x| x.into_path())
         .filter(|p| glob_set.is_match(p));
 
+    // Counts every occurrence of a link, even ones that share a target with
+    // an earlier one and so never get their own verify job.
     let mut n_links = 0;
+
     for path in file_iter {
         let path_str = path.to_str();
-        if let None = path_str {
+        if path_str.is_none() {
             // File path is not valid unicode, just skip.
             logger.warn(
                 &format!(
@@ -85,54 +172,111 @@ fn main() -> Result<(), ExitFailure> {
 
         logger.debug(&format!("Searching {}", path.display())[..])?;
 
-        searcher.search_path(
-            &matcher,
-            &path,
-            UTF8(|lnum, line| {
-                let mut captures = matcher.new_captures().unwrap();
-                matcher.captures_iter(line.as_bytes(), &mut captures, |c| {
-                    n_links += 1;
-                    let m = c.get(1).unwrap();
-                    let raw = line[m].to_string();
-
-                    let mut link = Link::new(String::from(path_str), lnum as usize, raw);
-
-                    let tx = tx.clone();
-                    let http_client = http_client.clone();
-                    pool.execute(move || {
-                        link.verify(http_client);
-                        tx.send(link).unwrap();
-                    });
-
-                    true
-                })?;
-
-                Ok(true)
-            }),
-        )?;
-    }
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                logger.warn(&format!("Couldn't read {}, skipping: {}", path.display(), e)[..])?;
+                continue;
+            }
+        };
+
+        for extract::Extracted { dest, line } in extract::extract(&path, &contents) {
+            n_links += 1;
+            let kind = LinkKind::classify(&dest, Path::new(path_str));
+            let key = kind.cache_key();
+            let site = (path_str.to_string(), line);
 
-    let mut n_bad_links = 0;
-    for link in rx.iter().take(n_links) {
-        match link.status.as_ref().unwrap() {
-            LinkStatus::Reachable => {
-                logger.info(&format!("✓ {}", link)[..])?;
+            // If this target has already been seen (at this or any other
+            // site), just record the new site and move on; otherwise this
+            // is the one thread that verifies it.
+            if cache.register(&key, site) {
+                let cache = cache.clone();
+                let verifier = verifier.clone();
+                pool.execute(move || {
+                    let mut link = Link::new(kind);
+                    link.verify(&verifier);
+                    cache.set_status(&key, link.status.unwrap());
+                });
             }
-            LinkStatus::Questionable(reason) => {
-                logger.warn(&format!("✓ {} ({})", link, reason)[..])?
+        }
+    }
+
+    pool.join();
+
+    let cache = Arc::try_unwrap(cache)
+        .unwrap_or_else(|_| panic!("verify jobs are still holding a reference to the cache"))
+        .into_inner();
+
+    let mut summary = Summary {
+        total_links: n_links,
+        unique_urls: cache.len(),
+        ..Summary::default()
+    };
+    let mut links = Vec::new();
+
+    for (url, val) in cache.iter() {
+        match &val.status {
+            LinkStatus::Unknown => {
+                // A verify job never ran for this URL; shouldn't happen since
+                // we join the pool before getting here.
+                logger.debug(&format!("? {} was never verified", url)[..])?;
+                continue;
             }
-            LinkStatus::Unreachable(reason) => {
-                n_bad_links += 1;
-                match reason {
-                    Some(s) => logger.error(&format!("✗ {} ({})", link, s)[..])?,
-                    None => logger.error(&format!("✗ {}", link)[..])?,
+            LinkStatus::Reachable => summary.reachable += val.sites.len(),
+            LinkStatus::Questionable(_) => summary.questionable += val.sites.len(),
+            LinkStatus::Unreachable(_) => summary.unreachable += val.sites.len(),
+        };
+
+        for (file, lnum) in &val.sites {
+            if let Format::Text = opt.format {
+                match &val.status {
+                    LinkStatus::Reachable => {
+                        logger.info(&format!("✓ {}:{}: {}", file, lnum, url)[..])?
+                    }
+                    LinkStatus::Questionable(reason) => logger.warn(
+                        &format!("✓ {}:{}: {} ({})", file, lnum, url, reason)[..],
+                    )?,
+                    LinkStatus::Unreachable(Some(reason)) => logger.error(
+                        &format!("✗ {}:{}: {} ({})", file, lnum, url, reason)[..],
+                    )?,
+                    LinkStatus::Unreachable(None) => {
+                        logger.error(&format!("✗ {}:{}: {}", file, lnum, url)[..])?
+                    }
+                    LinkStatus::Unknown => unreachable!(),
                 };
             }
-        };
+            links.push(ReportEntry::new(
+                file.clone(),
+                *lnum,
+                url.clone(),
+                &val.status,
+            ));
+        }
+    }
+
+    let n_failing = match opt.fail_on {
+        FailOn::Unreachable => summary.unreachable,
+        FailOn::Questionable => summary.unreachable + summary.questionable,
+    };
+
+    match opt.format {
+        Format::Text => {
+            logger.info(&format!(
+                "{} links ({} unique): {} reachable, {} questionable, {} unreachable",
+                summary.total_links,
+                summary.unique_urls,
+                summary.reachable,
+                summary.questionable,
+                summary.unreachable
+            )[..])?;
+        }
+        Format::Json => {
+            let report = Report { links, summary };
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
     }
 
-    if n_bad_links > 0 {
-        logger.error(&format!("Found {} bad links", n_bad_links)[..])?;
+    if n_failing > 0 {
         std::process::exit(1);
     }
 
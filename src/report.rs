@@ -0,0 +1,109 @@
+use serde_derive::Serialize;
+
+use crate::link::LinkStatus;
+
+/// A single link occurrence, shaped for the `--format json` report: one
+/// entry per site a URL was referenced from, not one per unique URL.
+#[derive(Debug, Serialize)]
+pub struct ReportEntry {
+    pub file: String,
+    pub line: usize,
+    pub url: String,
+    pub status: String,
+    pub reason: Option<String>,
+}
+
+impl ReportEntry {
+    pub fn new(file: String, line: usize, url: String, status: &LinkStatus) -> Self {
+        let (status, reason) = match status {
+            LinkStatus::Unknown => ("unknown", None),
+            LinkStatus::Reachable => ("reachable", None),
+            LinkStatus::Questionable(reason) => ("questionable", Some(reason.clone())),
+            LinkStatus::Unreachable(reason) => ("unreachable", reason.clone()),
+        };
+        ReportEntry {
+            file,
+            line,
+            url,
+            status: status.to_string(),
+            reason,
+        }
+    }
+}
+
+/// Counts rolled up across every link occurrence, printed (or serialized)
+/// once scanning is done.
+#[derive(Debug, Default, Serialize)]
+pub struct Summary {
+    pub total_links: usize,
+    pub unique_urls: usize,
+    pub reachable: usize,
+    pub questionable: usize,
+    pub unreachable: usize,
+}
+
+/// The full `--format json` payload: every checked link, plus the summary.
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub links: Vec<ReportEntry>,
+    pub summary: Summary,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_entry_maps_each_status_to_its_json_shape() {
+        let reachable = ReportEntry::new("a.md".into(), 1, "https://a".into(), &LinkStatus::Reachable);
+        assert_eq!(reachable.status, "reachable");
+        assert_eq!(reachable.reason, None);
+
+        let questionable = ReportEntry::new(
+            "a.md".into(),
+            2,
+            "https://b".into(),
+            &LinkStatus::Questionable("offline".to_string()),
+        );
+        assert_eq!(questionable.status, "questionable");
+        assert_eq!(questionable.reason, Some("offline".to_string()));
+
+        let unreachable = ReportEntry::new(
+            "a.md".into(),
+            3,
+            "https://c".into(),
+            &LinkStatus::Unreachable(Some("404".to_string())),
+        );
+        assert_eq!(unreachable.status, "unreachable");
+        assert_eq!(unreachable.reason, Some("404".to_string()));
+
+        let unreachable_no_reason =
+            ReportEntry::new("a.md".into(), 4, "https://d".into(), &LinkStatus::Unreachable(None));
+        assert_eq!(unreachable_no_reason.reason, None);
+    }
+
+    #[test]
+    fn report_serializes_to_the_expected_json_shape() {
+        let report = Report {
+            links: vec![ReportEntry::new(
+                "a.md".into(),
+                1,
+                "https://a".into(),
+                &LinkStatus::Reachable,
+            )],
+            summary: Summary {
+                total_links: 1,
+                unique_urls: 1,
+                reachable: 1,
+                questionable: 0,
+                unreachable: 0,
+            },
+        };
+
+        let value = serde_json::to_value(&report).unwrap();
+        assert_eq!(value["summary"]["total_links"], 1);
+        assert_eq!(value["links"][0]["file"], "a.md");
+        assert_eq!(value["links"][0]["status"], "reachable");
+        assert_eq!(value["links"][0]["reason"], serde_json::Value::Null);
+    }
+}